@@ -0,0 +1,152 @@
+//! Pluggable cryptographic backend.
+//!
+//! Hawk needs exactly two primitives: a digest (for payload hashes) and an HMAC
+//! (for request MACs). Rather than hard-wire these to `ring`, the default
+//! payload hasher routes them through a globally-registered [`Cryptographer`]
+//! (the separate `rustcrypto` backend does not). A `ring`-backed implementation
+//! is installed by default, so ordinary users need do nothing; embedders who
+//! want another backend (platform crypto on iOS, Android or WASM) can install
+//! their own with [`set_cryptographer`]. The `ring` default is always compiled
+//! in.
+
+use std::sync::OnceLock;
+
+use ring::{digest, hmac};
+
+/// The digest algorithms Hawk understands.
+///
+/// This is a crate-defined enum rather than a backend type so that the public
+/// API does not leak whichever crypto library happens to be in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    /// The length, in bytes, of a digest produced by this algorithm.
+    pub fn output_len(&self) -> usize {
+        match *self {
+            DigestAlgorithm::Sha256 => 32,
+            DigestAlgorithm::Sha384 => 48,
+            DigestAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+/// An incremental digest computation.
+///
+/// Obtained from [`Cryptographer::hasher`]. Feed data with [`update`](Hasher::update)
+/// and consume it with [`finish`](Hasher::finish).
+pub trait Hasher {
+    /// Update the digest with more data.
+    fn update(&mut self, data: &[u8]);
+
+    /// Finish the computation and return the raw digest bytes.
+    fn finish(self: Box<Self>) -> Vec<u8>;
+}
+
+/// An HMAC key bound to a particular algorithm.
+///
+/// Obtained from [`Cryptographer::import_hmac_key`].
+pub trait HmacKey {
+    /// Sign `data`, returning the raw tag.
+    fn sign(&self, data: &[u8]) -> Vec<u8>;
+
+    /// Verify that `signature` is a valid tag for `data`.
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool;
+}
+
+/// A backend supplying the digest and HMAC primitives Hawk relies on.
+///
+/// Install a custom implementation with [`set_cryptographer`]; the default is
+/// [`RingCryptographer`].
+pub trait Cryptographer: Sync + Send {
+    /// Start a new incremental digest using `algorithm`.
+    fn hasher(&self, algorithm: DigestAlgorithm) -> Box<dyn Hasher>;
+
+    /// Import `key` as an HMAC key bound to `algorithm`.
+    fn import_hmac_key(&self, algorithm: DigestAlgorithm, key: &[u8]) -> Box<dyn HmacKey>;
+}
+
+/// The `ring`-backed [`Cryptographer`] installed by default.
+pub struct RingCryptographer;
+
+impl Cryptographer for RingCryptographer {
+    fn hasher(&self, algorithm: DigestAlgorithm) -> Box<dyn Hasher> {
+        let alg = ring_digest(algorithm);
+        Box::new(RingHasher {
+            context: digest::Context::new(alg),
+            output_len: alg.output_len,
+        })
+    }
+
+    fn import_hmac_key(&self, algorithm: DigestAlgorithm, key: &[u8]) -> Box<dyn HmacKey> {
+        let alg = match algorithm {
+            DigestAlgorithm::Sha256 => hmac::HMAC_SHA256,
+            DigestAlgorithm::Sha384 => hmac::HMAC_SHA384,
+            DigestAlgorithm::Sha512 => hmac::HMAC_SHA512,
+        };
+        Box::new(RingHmacKey(hmac::Key::new(alg, key)))
+    }
+}
+
+/// Map a [`DigestAlgorithm`] onto the matching `ring` algorithm.
+fn ring_digest(algorithm: DigestAlgorithm) -> &'static digest::Algorithm {
+    match algorithm {
+        DigestAlgorithm::Sha256 => &digest::SHA256,
+        DigestAlgorithm::Sha384 => &digest::SHA384,
+        DigestAlgorithm::Sha512 => &digest::SHA512,
+    }
+}
+
+struct RingHasher {
+    context: digest::Context,
+    output_len: usize,
+}
+
+impl Hasher for RingHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.context.update(data);
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        let digest = self.context.finish();
+        let mut rv = vec![0; self.output_len];
+        rv.clone_from_slice(digest.as_ref());
+        rv
+    }
+}
+
+struct RingHmacKey(hmac::Key);
+
+impl HmacKey for RingHmacKey {
+    fn sign(&self, data: &[u8]) -> Vec<u8> {
+        hmac::sign(&self.0, data).as_ref().to_vec()
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        hmac::verify(&self.0, data, signature).is_ok()
+    }
+}
+
+static CRYPTOGRAPHER: OnceLock<&'static dyn Cryptographer> = OnceLock::new();
+
+static DEFAULT_CRYPTOGRAPHER: RingCryptographer = RingCryptographer;
+
+/// Install a custom [`Cryptographer`] for the whole process.
+///
+/// This may only be called once, and only before the first crypto operation
+/// forces the default to be installed. Returns `Err` if a cryptographer has
+/// already been set, leaving the existing one in place.
+pub fn set_cryptographer(
+    cryptographer: &'static dyn Cryptographer,
+) -> Result<(), &'static dyn Cryptographer> {
+    CRYPTOGRAPHER.set(cryptographer)
+}
+
+/// The cryptographer in effect, installing the `ring`-backed default on first use.
+pub(crate) fn cryptographer() -> &'static dyn Cryptographer {
+    *CRYPTOGRAPHER.get_or_init(|| &DEFAULT_CRYPTOGRAPHER)
+}
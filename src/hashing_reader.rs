@@ -0,0 +1,138 @@
+use std::io::{self, Read};
+
+use crate::payload::PayloadHasher;
+
+/// A reader adapter that computes the Hawk payload hash as bytes flow through it.
+///
+/// Wrap any [`Read`] and read from the `HashingReader` as usual; every byte that
+/// passes through is fed into the held [`PayloadHasher`]. When the body has been
+/// fully consumed call [`finish`](HashingReader::finish) to append the trailing
+/// newline and obtain both the digest and the total number of bytes seen. This
+/// lets a server verify a body hash while proxying it, without a second pass over
+/// the data.
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: PayloadHasher,
+    bytes: u64,
+}
+
+impl<R> HashingReader<R> {
+    /// Wrap `inner`, hashing everything read from it into `hasher`.
+    pub fn new(inner: R, hasher: PayloadHasher) -> Self {
+        HashingReader {
+            inner,
+            hasher,
+            bytes: 0,
+        }
+    }
+
+    /// The number of payload bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Finish hashing and return the digest together with the total byte count.
+    pub fn finish(self) -> (Vec<u8>, u64) {
+        (self.hasher.finish(), self.bytes)
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.hasher.update(&buf[..n]);
+            self.bytes += n as u64;
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynchronous {
+    use std::pin::Pin;
+    use std::task::{ready, Context, Poll};
+
+    use pin_project::pin_project;
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    use crate::payload::PayloadHasher;
+
+    /// The [`tokio::io::AsyncRead`] counterpart of [`HashingReader`](super::HashingReader).
+    #[pin_project]
+    pub struct AsyncHashingReader<R> {
+        #[pin]
+        inner: R,
+        hasher: PayloadHasher,
+        bytes: u64,
+    }
+
+    impl<R> AsyncHashingReader<R> {
+        /// Wrap `inner`, hashing everything read from it into `hasher`.
+        pub fn new(inner: R, hasher: PayloadHasher) -> Self {
+            AsyncHashingReader {
+                inner,
+                hasher,
+                bytes: 0,
+            }
+        }
+
+        /// The number of payload bytes read so far.
+        pub fn bytes_read(&self) -> u64 {
+            self.bytes
+        }
+
+        /// Finish hashing and return the digest together with the total byte count.
+        pub fn finish(self) -> (Vec<u8>, u64) {
+            (self.hasher.finish(), self.bytes)
+        }
+    }
+
+    impl<R: AsyncRead> AsyncRead for AsyncHashingReader<R> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            let this = self.project();
+            let before = buf.filled().len();
+            ready!(this.inner.poll_read(cx, buf))?;
+            let filled = buf.filled();
+            if filled.len() > before {
+                let new = &filled[before..];
+                this.hasher.update(new);
+                *this.bytes += new.len() as u64;
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use asynchronous::AsyncHashingReader;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::HashingReader;
+    use crate::crypto::DigestAlgorithm;
+    use crate::payload::PayloadHasher;
+
+    #[test]
+    fn matches_oneshot_hash() {
+        let mut reader = HashingReader::new(
+            &b"p\xc3\xa0yload"[..],
+            PayloadHasher::new("text/plain", DigestAlgorithm::Sha256),
+        );
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body).unwrap();
+
+        let (digest, bytes) = reader.finish();
+        assert_eq!(bytes, body.len() as u64);
+        assert_eq!(
+            digest,
+            PayloadHasher::hash("text/plain", DigestAlgorithm::Sha256, &body)
+        );
+    }
+}
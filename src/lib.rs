@@ -0,0 +1,8 @@
+#[cfg(feature = "rustcrypto")]
+extern crate alloc;
+
+pub mod crypto;
+pub mod hashing_reader;
+pub mod payload;
+#[cfg(feature = "rustcrypto")]
+pub mod rustcrypto;
@@ -1,22 +1,22 @@
-use ring::digest;
+use ring::constant_time;
+
+use crate::crypto::{self, DigestAlgorithm, Hasher};
 
 /// A utility for hashing payloads. Feed your entity body to this, then pass the `finish`
 /// result to a request or response.
 pub struct PayloadHasher {
-    context: digest::Context,
-    algorithm: &'static digest::Algorithm,
+    hasher: Box<dyn Hasher>,
 }
 
 impl PayloadHasher {
     /// Create a new PayloadHasher. The `content_type` should be lower-case and should
     /// not include parameters. The digest is assumed to be the same as the digest used
     /// for the credentials in the request.
-    pub fn new<B>(content_type: B, algorithm: &'static digest::Algorithm) -> Self
+    pub fn new<B>(content_type: B, algorithm: DigestAlgorithm) -> Self
         where B: AsRef<[u8]>
     {
         let mut hasher = PayloadHasher {
-            context: digest::Context::new(algorithm),
-            algorithm,
+            hasher: crypto::cryptographer().hasher(algorithm),
         };
         hasher.update(b"hawk.1.payload\n");
         hasher.update(content_type.as_ref());
@@ -26,7 +26,7 @@ impl PayloadHasher {
 
     /// Hash a single value and return it
     pub fn hash<B1, B2>(content_type: B1,
-                        algorithm: &'static digest::Algorithm,
+                        algorithm: DigestAlgorithm,
                         payload: B2)
                         -> Vec<u8>
         where B1: AsRef<[u8]>,
@@ -41,41 +41,129 @@ impl PayloadHasher {
     pub fn update<B>(&mut self, data: B)
         where B: AsRef<[u8]>
     {
-        self.context.update(data.as_ref());
+        self.hasher.update(data.as_ref());
     }
 
     /// Finish hashing and return the result
     ///
-    /// Note that this appends a newline to the payload, as does the JS Hawk implementaiton.
+    /// Note that this appends a newline to the payload, as does the JS Hawk implementation.
     pub fn finish(mut self) -> Vec<u8> {
         self.update(b"\n");
-        let digest = self.context.finish();
-        let mut rv = vec![0; self.algorithm.output_len];
-        rv.clone_from_slice(digest.as_ref());
-        rv
+        self.hasher.finish()
+    }
+
+    /// Finish hashing and check the result against `expected` in constant time.
+    ///
+    /// Returns `false` on a length mismatch, and otherwise compares the digests with
+    /// a constant-time primitive so that integrators do not leak timing information by
+    /// comparing the `finish` output with `==`.
+    pub fn verify(self, expected: &[u8]) -> bool {
+        constant_time::verify_slices_are_equal(&self.finish(), expected).is_ok()
+    }
+
+    /// Hash a single value and check it against `expected` in constant time.
+    pub fn verify_oneshot<B1, B2>(content_type: B1,
+                                  algorithm: DigestAlgorithm,
+                                  payload: B2,
+                                  expected: &[u8])
+                                  -> bool
+        where B1: AsRef<[u8]>,
+              B2: AsRef<[u8]>
+    {
+        let mut hasher = PayloadHasher::new(content_type, algorithm);
+        hasher.update(payload);
+        hasher.verify(expected)
+    }
+}
+
+/// A payload hasher whose algorithm is not yet known.
+///
+/// Some request pipelines see the entity body before the credentials (and hence
+/// the digest algorithm) have been resolved. `PayloadHashBuffer` accepts `update`
+/// calls in the meantime, buffering the raw bytes; once [`start`](PayloadHashBuffer::start)
+/// supplies the algorithm it constructs the real [`PayloadHasher`], replays the
+/// buffered bytes through it (prelude included) and hashes incrementally from then
+/// on. This saves callers from having to re-read the body once credentials are
+/// selected.
+pub struct PayloadHashBuffer {
+    content_type: Vec<u8>,
+    state: State,
+}
+
+enum State {
+    /// No algorithm yet; raw payload bytes are buffered verbatim.
+    Buffering(Vec<u8>),
+    /// The algorithm is known and hashing proceeds incrementally.
+    Hashing(PayloadHasher),
+}
+
+impl PayloadHashBuffer {
+    /// Create a buffer for the given `content_type`, deferring the algorithm.
+    pub fn new<B>(content_type: B) -> Self
+        where B: AsRef<[u8]>
+    {
+        PayloadHashBuffer {
+            content_type: content_type.as_ref().to_vec(),
+            state: State::Buffering(Vec::new()),
+        }
+    }
+
+    /// Update with new data, buffering it until [`start`](PayloadHashBuffer::start)
+    /// is called and hashing it directly afterwards.
+    pub fn update<B>(&mut self, data: B)
+        where B: AsRef<[u8]>
+    {
+        match self.state {
+            State::Buffering(ref mut buf) => buf.extend_from_slice(data.as_ref()),
+            State::Hashing(ref mut hasher) => hasher.update(data),
+        }
+    }
+
+    /// Fix the digest algorithm and replay any buffered bytes through a real hasher.
+    ///
+    /// Has no effect if the algorithm has already been chosen.
+    pub fn start(&mut self, algorithm: DigestAlgorithm) {
+        if let State::Buffering(ref mut buf) = self.state {
+            let buffered = std::mem::take(buf);
+            let mut hasher = PayloadHasher::new(&self.content_type, algorithm);
+            hasher.update(buffered);
+            self.state = State::Hashing(hasher);
+        }
+    }
+
+    /// Finish hashing and return the result.
+    ///
+    /// Returns `None` if [`start`](PayloadHashBuffer::start) was never called, since the
+    /// digest algorithm is then unknown; otherwise returns the digest, whether or not any
+    /// data was buffered beforehand.
+    pub fn finish(self) -> Option<Vec<u8>> {
+        match self.state {
+            State::Hashing(hasher) => Some(hasher.finish()),
+            State::Buffering(_) => None,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::PayloadHasher;
-    use ring::digest::SHA256;
+    use super::{PayloadHashBuffer, PayloadHasher};
+    use crate::crypto::DigestAlgorithm;
 
     #[test]
     fn hash_consistency() {
-        let mut hasher1 = PayloadHasher::new("text/plain", &SHA256);
+        let mut hasher1 = PayloadHasher::new("text/plain", DigestAlgorithm::Sha256);
         hasher1.update("pày");
         hasher1.update("load");
         let hash1 = hasher1.finish();
 
-        let mut hasher2 = PayloadHasher::new("text/plain", &SHA256);
+        let mut hasher2 = PayloadHasher::new("text/plain", DigestAlgorithm::Sha256);
         hasher2.update("pàyload");
         let hash2 = hasher2.finish();
 
-        let hash3 = PayloadHasher::hash("text/plain", &SHA256, "pàyload");
+        let hash3 = PayloadHasher::hash("text/plain", DigestAlgorithm::Sha256, "pàyload");
 
         let hash4 = // "pàyload" as utf-8 bytes
-            PayloadHasher::hash("text/plain", &SHA256, vec![112, 195, 160, 121, 108, 111, 97, 100]);
+            PayloadHasher::hash("text/plain", DigestAlgorithm::Sha256, vec![112, 195, 160, 121, 108, 111, 97, 100]);
 
         assert_eq!(hash1,
                    vec![228, 238, 241, 224, 235, 114, 158, 112, 211, 254, 118, 89, 25, 236, 87,
@@ -85,4 +173,34 @@ mod tests {
         assert_eq!(hash3, hash1);
         assert_eq!(hash4, hash1);
     }
+
+    #[test]
+    fn deferred_matches_eager() {
+        let mut buffer = PayloadHashBuffer::new("text/plain");
+        buffer.update("pày");
+        buffer.start(DigestAlgorithm::Sha256);
+        buffer.update("load");
+        let buffered = buffer.finish().unwrap();
+
+        assert_eq!(buffered,
+                   PayloadHasher::hash("text/plain", DigestAlgorithm::Sha256, "pàyload"));
+    }
+
+    #[test]
+    fn verify_oneshot() {
+        let expected = PayloadHasher::hash("text/plain", DigestAlgorithm::Sha256, "pàyload");
+
+        assert!(PayloadHasher::verify_oneshot("text/plain",
+                                              DigestAlgorithm::Sha256,
+                                              "pàyload",
+                                              &expected));
+        assert!(!PayloadHasher::verify_oneshot("text/plain",
+                                               DigestAlgorithm::Sha256,
+                                               "wrong",
+                                               &expected));
+        assert!(!PayloadHasher::verify_oneshot("text/plain",
+                                               DigestAlgorithm::Sha256,
+                                               "pàyload",
+                                               b"too short"));
+    }
 }
@@ -0,0 +1,61 @@
+//! A [`PayloadHasher`] backed by the RustCrypto [`digest::Digest`] trait.
+//!
+//! This backend lets Hawk peers negotiate algorithms `ring` does not ship —
+//! BLAKE2b, SHA-3/Keccak, RIPEMD. The module itself uses only `alloc` and
+//! `digest`, but the rest of the crate (including [`crate::crypto`]) is always
+//! compiled and depends on `ring` and `std`, so enabling this backend does not
+//! remove those dependencies. The algorithm is selected by the digest type `D` rather than a run-time
+//! enum, so the public `hash`/`new`/`update`/`finish` surface is identical to the
+//! default hasher, just parameterized.
+
+use alloc::vec::Vec;
+
+use digest::Digest;
+
+/// A utility for hashing payloads with a RustCrypto digest. Feed your entity body
+/// to this, then pass the `finish` result to a request or response.
+pub struct PayloadHasher<D> {
+    digest: D,
+}
+
+impl<D> PayloadHasher<D>
+    where D: Digest
+{
+    /// Create a new PayloadHasher. The `content_type` should be lower-case and should
+    /// not include parameters. The digest algorithm is the one named by `D`, which is
+    /// assumed to match the digest used for the credentials in the request.
+    pub fn new<B>(content_type: B) -> Self
+        where B: AsRef<[u8]>
+    {
+        let mut hasher = PayloadHasher { digest: D::new() };
+        hasher.update(b"hawk.1.payload\n");
+        hasher.update(content_type.as_ref());
+        hasher.update(b"\n");
+        hasher
+    }
+
+    /// Hash a single value and return it
+    pub fn hash<B1, B2>(content_type: B1, payload: B2) -> Vec<u8>
+        where B1: AsRef<[u8]>,
+              B2: AsRef<[u8]>
+    {
+        let mut hasher = PayloadHasher::<D>::new(content_type);
+        hasher.update(payload);
+        hasher.finish()
+    }
+
+    /// Update the hash with new data.
+    pub fn update<B>(&mut self, data: B)
+        where B: AsRef<[u8]>
+    {
+        Digest::update(&mut self.digest, data.as_ref());
+    }
+
+    /// Finish hashing and return the result
+    ///
+    /// Note that this appends a newline to the payload, as does the JS Hawk implementation.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.update(b"\n");
+        self.digest.finalize().to_vec()
+    }
+}